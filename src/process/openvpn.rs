@@ -4,21 +4,50 @@ use clonablechild::{ClonableChild, ChildExt};
 
 use net::{RemoteAddr, ToRemoteAddrs};
 
+use tempfile::NamedTempFile;
+
 use std::ffi::{OsString, OsStr};
 use std::fmt;
-use std::io;
-use std::path::{Path, PathBuf};
+use std::io::{self, Write};
+use std::path::Path;
 use std::process::{Command, Child, Stdio};
+use std::sync::Arc;
+
+#[cfg(unix)]
+use std::fs::Permissions;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// The second argument to `--management`: either a TCP port, or `unix`, which makes OpenVPN treat
+/// `addr_or_path` as the path to a unix domain socket instead of a host.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ManagementSocket {
+    Port(u16),
+    Unix,
+}
+
+/// The transport protocol OpenVPN should use for the tunnel, set via `OpenVpnCommand::protocol`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
 
 /// An OpenVPN process builder, providing control over the different arguments that the OpenVPN
 /// binary accepts.
 #[derive(Clone)]
 pub struct OpenVpnCommand {
     openvpn_bin: OsString,
-    config: Option<PathBuf>,
-    remotes: Vec<RemoteAddr>,
-    plugin: Option<(PathBuf, Vec<String>)>,
+    /// Every option OpenVPN will be started with, grouped so that a flag and its arguments stay
+    /// together (`options[n][0]` is the flag, e.g. `--remote`, and the rest are its arguments).
+    /// Keeping options grouped like this lets them be rendered either as a flat argument list or
+    /// as lines in an OpenVPN configuration file.
+    options: Vec<Vec<OsString>>,
     pipe_output: bool,
+    /// The temporary `--auth-user-pass` file, if `user_pass` was called. Kept alive here so it
+    /// isn't deleted before the spawned `Child` has read it; wrapped in `Arc` because this type
+    /// derives `Clone` but a temp file must not be deleted until every clone is gone.
+    user_pass_file: Option<Arc<NamedTempFile>>,
 }
 
 impl OpenVpnCommand {
@@ -27,32 +56,97 @@ impl OpenVpnCommand {
     pub fn new<P: AsRef<OsStr>>(openvpn_bin: P) -> Self {
         OpenVpnCommand {
             openvpn_bin: OsString::from(openvpn_bin.as_ref()),
-            config: None,
-            remotes: vec![],
-            plugin: None,
+            options: vec![],
             pipe_output: true,
+            user_pass_file: None,
         }
     }
 
     /// Sets what configuration file will be given to OpenVPN
     pub fn config<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
-        self.config = Some(path.as_ref().to_path_buf());
+        self.set_option("--config", vec![OsString::from(path.as_ref().as_os_str())]);
         self
     }
 
     /// Sets the addresses that OpenVPN will connect to. See OpenVPN documentation for how multiple
     /// remotes are handled.
     pub fn remotes<A: ToRemoteAddrs>(&mut self, remotes: A) -> io::Result<&mut Self> {
-        self.remotes = remotes.to_remote_addrs()?.collect();
+        let remotes: Vec<RemoteAddr> = remotes.to_remote_addrs()?.collect();
+        self.clear_option("--remote");
+        for remote in remotes {
+            self.push_option("--remote", vec![
+                OsString::from(remote.address()),
+                OsString::from(remote.port().to_string()),
+            ]);
+        }
         Ok(self)
     }
 
     /// Sets a plugin and its arguments that OpenVPN will be started with.
     pub fn plugin<P: AsRef<Path>>(&mut self, path: P, args: Vec<String>) -> &mut Self {
-        self.plugin = Some((path.as_ref().to_path_buf(), args));
+        let mut group_args = vec![OsString::from(path.as_ref().as_os_str())];
+        group_args.extend(args.into_iter().map(OsString::from));
+        self.set_option("--plugin", group_args);
         self
     }
 
+    /// Enables OpenVPN's management interface, listening at `addr_or_path` on either a TCP port
+    /// or, with `ManagementSocket::Unix`, as a unix domain socket. `client` makes OpenVPN connect
+    /// out to the management interface instead of listening on it, and `hold` makes it wait for
+    /// `hold release` before bringing the tunnel up. If `query_passwords` is set, OpenVPN requests
+    /// credentials over the socket (`--management-query-passwords`) instead of reading them from
+    /// disk, and callers should use `management::ManagementClient::send_credentials` to answer
+    /// the request. Don't set `query_passwords` together with `user_pass`: OpenVPN prefers the
+    /// management query over `--auth-user-pass`, which would silently make `user_pass` a no-op.
+    pub fn management_interface<A: AsRef<OsStr>>(&mut self,
+                                                  addr_or_path: A,
+                                                  port_or_unix: ManagementSocket,
+                                                  client: bool,
+                                                  hold: bool,
+                                                  query_passwords: bool)
+                                                  -> &mut Self {
+        let socket_arg = match port_or_unix {
+            ManagementSocket::Port(port) => OsString::from(port.to_string()),
+            ManagementSocket::Unix => OsString::from("unix"),
+        };
+        self.set_option("--management", vec![OsString::from(addr_or_path.as_ref()), socket_arg]);
+        if query_passwords {
+            self.set_option("--management-query-passwords", vec![]);
+        } else {
+            self.clear_option("--management-query-passwords");
+        }
+        if client {
+            self.set_option("--management-client", vec![]);
+        } else {
+            self.clear_option("--management-client");
+        }
+        if hold {
+            self.set_option("--management-hold", vec![]);
+        } else {
+            self.clear_option("--management-hold");
+        }
+        self
+    }
+
+    /// Writes `username` and `password` to a temporary file with `0600` permissions and passes it
+    /// to OpenVPN via `--auth-user-pass`, instead of putting the credentials on the command line
+    /// where they would be visible to other processes through `ps` and leaked by this type's
+    /// `Display` impl. The temp file is kept alive for as long as this `OpenVpnCommand` does, and
+    /// is removed automatically once dropped. Don't combine this with
+    /// `management_interface(.., query_passwords: true)`: OpenVPN prefers the management query
+    /// over `--auth-user-pass`, which would make this a silent no-op.
+    pub fn user_pass(&mut self, username: &str, password: &str) -> io::Result<&mut Self> {
+        let mut file = NamedTempFile::new()?;
+        #[cfg(unix)]
+        file.as_file().set_permissions(Permissions::from_mode(0o600))?;
+        writeln!(file, "{}", username)?;
+        writeln!(file, "{}", password)?;
+        file.flush()?;
+        self.set_option("--auth-user-pass", vec![OsString::from(file.path())]);
+        self.user_pass_file = Some(Arc::new(file));
+        Ok(self)
+    }
+
     /// If piping the standard streams, stdout and stderr will be available to the parent process.
     /// This is the default behavior. If you want the equivalence of attaching the child streams to
     /// /dev/null, invoke this method with false.
@@ -61,7 +155,10 @@ impl OpenVpnCommand {
         self
     }
 
-    /// Executes the OpenVPN process as a child process, returning a handle to it.
+    /// Executes the OpenVPN process as a child process, returning a handle to it. Because this
+    /// only borrows `self`, any temp files owned by this `OpenVpnCommand` (e.g. from `user_pass`)
+    /// must be kept alive by the caller for as long as the child runs. Use `into_spawn` to avoid
+    /// that requirement.
     pub fn spawn(&self) -> io::Result<Child> {
         let mut command = self.create_command();
         let args = self.get_arguments();
@@ -69,6 +166,16 @@ impl OpenVpnCommand {
         command.spawn()
     }
 
+    /// Executes the OpenVPN process, returning an `OwningChild` that owns both the running child
+    /// and this `OpenVpnCommand`. Moving the command in alongside the child keeps any temp files
+    /// it owns (a generated `--auth-user-pass` file, for instance) alive for exactly as long as
+    /// the child is, so the result can be moved into a struct or a supervision map without
+    /// self-reference headaches or dangling temp paths.
+    pub fn into_spawn(self) -> io::Result<OwningChild> {
+        let child = self.spawn()?.into_clonable();
+        Ok(OwningChild { child, command: self })
+    }
+
     fn create_command(&self) -> Command {
         let mut command = Command::new(&self.openvpn_bin);
         command.stdin(Stdio::null())
@@ -87,22 +194,128 @@ impl OpenVpnCommand {
 
     /// Returns all arguments that the subprocess would be spawned with.
     pub fn get_arguments(&self) -> Vec<OsString> {
-        let mut args = vec![];
-        if let Some(ref config) = self.config {
-            args.push(OsString::from("--config"));
-            args.push(OsString::from(config.as_os_str()));
-        }
-        for remote in &self.remotes {
-            args.push(OsString::from("--remote"));
-            args.push(OsString::from(remote.address()));
-            args.push(OsString::from(remote.port().to_string()));
+        self.options.iter().flat_map(|group| group.iter().cloned()).collect()
+    }
+
+    /// Renders the options as an OpenVPN configuration file. Each option is written on its own
+    /// line without the leading `--`, e.g. `remote example.com 1194`, the same format OpenVPN
+    /// itself reads from a `.conf` file. Arguments containing whitespace (e.g. a Windows
+    /// `C:\Program Files\...` path) are quoted the same way `Display` quotes them, so OpenVPN's
+    /// config parser doesn't split them into extra tokens.
+    pub fn to_config_file(&self) -> String {
+        let mut config = String::new();
+        for group in &self.options {
+            let mut tokens = group.iter();
+            if let Some(flag) = tokens.next() {
+                config.push_str(flag.to_string_lossy().trim_start_matches("--"));
+                for arg in tokens {
+                    config.push(' ');
+                    write_quoted_token(&mut config, &arg.to_string_lossy())
+                        .expect("writing to a String never fails");
+                }
+            }
+            config.push('\n');
         }
-        if let Some((ref path, ref plugin_args)) = self.plugin {
-            args.push(OsString::from("--plugin"));
-            args.push(OsString::from(path));
-            args.extend(plugin_args.iter().map(|arg| OsString::from(arg)));
+        config
+    }
+
+    /// Appends an arbitrary OpenVPN option, e.g.
+    /// `option("--cipher", vec![OsString::from("AES-256-GCM")])`. An escape hatch for options
+    /// this builder doesn't model with a typed setter; it keeps its position in the option list
+    /// relative to whichever typed setters were called before or after it, so config-file
+    /// rendering stays faithful to call order.
+    pub fn option<F: AsRef<OsStr>>(&mut self, flag: F, args: Vec<OsString>) -> &mut Self {
+        self.push_option(flag, args);
+        self
+    }
+
+    /// Appends a batch of arbitrary options via `option`, in iteration order.
+    pub fn options<I>(&mut self, options: I) -> &mut Self
+        where I: IntoIterator<Item = (OsString, Vec<OsString>)>
+    {
+        for (flag, args) in options {
+            self.push_option(flag, args);
         }
-        args
+        self
+    }
+
+    /// Sets the transport protocol OpenVPN will use to connect, i.e. `--proto tcp`/`--proto udp`.
+    pub fn protocol(&mut self, protocol: Protocol) -> &mut Self {
+        let value = match protocol {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        };
+        self.set_option("--proto", vec![OsString::from(value)]);
+        self
+    }
+
+    /// Sets the script OpenVPN runs once the tunnel interface comes up (`--up`). Commonly used to
+    /// reconfigure firewall rules or DNS once the tunnel is ready.
+    pub fn up<P: AsRef<OsStr>>(&mut self, script: P) -> &mut Self {
+        self.set_option("--up", vec![OsString::from(script.as_ref())]);
+        self
+    }
+
+    /// Sets the script OpenVPN runs when the tunnel interface is torn down (`--down`).
+    pub fn down<P: AsRef<OsStr>>(&mut self, script: P) -> &mut Self {
+        self.set_option("--down", vec![OsString::from(script.as_ref())]);
+        self
+    }
+
+    /// Sets the script OpenVPN runs whenever a route is added (`--route-up`).
+    pub fn route_up<P: AsRef<OsStr>>(&mut self, script: P) -> &mut Self {
+        self.set_option("--route-up", vec![OsString::from(script.as_ref())]);
+        self
+    }
+
+    /// Sets the `--script-security` level required for the `up`/`down`/`route_up` scripts to run.
+    pub fn script_security(&mut self, level: u8) -> &mut Self {
+        self.set_option("--script-security", vec![OsString::from(level.to_string())]);
+        self
+    }
+
+    /// Replaces any existing option(s) for `flag` with a single option group `flag` + `args`.
+    fn set_option<F: AsRef<OsStr>>(&mut self, flag: F, args: Vec<OsString>) {
+        self.clear_option(flag.as_ref());
+        self.push_option(flag, args);
+    }
+
+    /// Appends a new option group for `flag`, without removing any existing ones.
+    fn push_option<F: AsRef<OsStr>>(&mut self, flag: F, args: Vec<OsString>) {
+        let mut group = vec![OsString::from(flag.as_ref())];
+        group.extend(args);
+        self.options.push(group);
+    }
+
+    /// Removes every existing option group for `flag`.
+    fn clear_option<F: AsRef<OsStr>>(&mut self, flag: F) {
+        let flag = flag.as_ref();
+        self.options.retain(|group| group[0] != flag);
+    }
+}
+
+/// A running OpenVPN child process together with the `OpenVpnCommand` that spawned it, returned
+/// by `OpenVpnCommand::into_spawn`. Holding both avoids self-referential lifetimes while still
+/// keeping any temp files the command owns alive for as long as the child is.
+pub struct OwningChild {
+    child: ClonableChild,
+    command: OpenVpnCommand,
+}
+
+impl OwningChild {
+    /// The running child process.
+    pub fn child(&self) -> &ClonableChild {
+        &self.child
+    }
+
+    /// A mutable handle to the running child process.
+    pub fn child_mut(&mut self) -> &mut ClonableChild {
+        &mut self.child
+    }
+
+    /// The `OpenVpnCommand` that was used to spawn this child.
+    pub fn command(&self) -> &OpenVpnCommand {
+        &self.command
     }
 }
 
@@ -120,13 +333,20 @@ impl fmt::Display for OpenVpnCommand {
 
 fn write_argument(fmt: &mut fmt::Formatter, arg: &str) -> fmt::Result {
     fmt.write_str(" ")?;
-    let quote = arg.contains(char::is_whitespace);
+    write_quoted_token(fmt, arg)
+}
+
+/// Writes `token` to `writer`, wrapping it in `"` quotes if it contains whitespace, so a single
+/// token never gets split into several by whatever reads it back (a shell, or OpenVPN's own
+/// config file parser).
+fn write_quoted_token<W: fmt::Write>(writer: &mut W, token: &str) -> fmt::Result {
+    let quote = token.contains(char::is_whitespace);
     if quote {
-        fmt.write_str("\"")?;
+        writer.write_str("\"")?;
     }
-    fmt.write_str(arg)?;
+    writer.write_str(token)?;
     if quote {
-        fmt.write_str("\"")?;
+        writer.write_str("\"")?;
     }
     Ok(())
 }
@@ -141,11 +361,180 @@ impl ChildSpawner for OpenVpnCommand {
 }
 
 
+/// A client for OpenVPN's management interface, used to supervise a spawned OpenVPN process
+/// instead of scraping its stdout: reading state transitions as they happen, querying the
+/// connection status on demand, and issuing commands such as releasing a `--management-hold` or
+/// answering a password request triggered by `--management-query-passwords`.
+pub mod management {
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::path::Path;
+
+    #[cfg(unix)]
+    use std::os::unix::net::UnixStream;
+
+    /// A single line of output from the management interface. Notifications pushed by OpenVPN
+    /// are prefixed with `>`, e.g. `>STATE:...` or `>HOLD:...`; anything else is a reply to a
+    /// command that was written to the socket.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum ManagementEvent {
+        /// A `>STATE:` notification, with the part after the prefix.
+        State(String),
+        /// A `>HOLD:` notification, signaling that OpenVPN is waiting for `release_hold`.
+        Hold(String),
+        /// A `>PASSWORD:` notification, requesting credentials be written back to the socket.
+        PasswordRequest(String),
+        /// Any other line read from the socket.
+        Other(String),
+    }
+
+    impl ManagementEvent {
+        fn parse(line: &str) -> ManagementEvent {
+            if let Some(rest) = line.strip_prefix(">STATE:") {
+                ManagementEvent::State(rest.to_owned())
+            } else if let Some(rest) = line.strip_prefix(">HOLD:") {
+                ManagementEvent::Hold(rest.to_owned())
+            } else if let Some(rest) = line.strip_prefix(">PASSWORD:") {
+                ManagementEvent::PasswordRequest(rest.to_owned())
+            } else {
+                ManagementEvent::Other(line.to_owned())
+            }
+        }
+    }
+
+    /// A connection to an OpenVPN management interface socket, generic over the transport (a TCP
+    /// socket or, on unix platforms, a unix domain socket).
+    pub struct ManagementClient<S> {
+        connection: BufReader<S>,
+    }
+
+    impl ManagementClient<TcpStream> {
+        /// Connects to a management interface listening on a TCP socket.
+        pub fn connect_tcp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+            Ok(ManagementClient { connection: BufReader::new(TcpStream::connect(addr)?) })
+        }
+    }
+
+    #[cfg(unix)]
+    impl ManagementClient<UnixStream> {
+        /// Connects to a management interface listening on a unix domain socket.
+        pub fn connect_unix<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+            Ok(ManagementClient { connection: BufReader::new(UnixStream::connect(path)?) })
+        }
+    }
+
+    impl<S: io::Read + Write> ManagementClient<S> {
+        /// Blocks until the next line is available on the socket and parses it as an event.
+        pub fn read_event(&mut self) -> io::Result<ManagementEvent> {
+            let mut line = String::new();
+            self.connection.read_line(&mut line)?;
+            Ok(ManagementEvent::parse(line.trim_end_matches(['\r', '\n'])))
+        }
+
+        /// Sends the `status` command and returns OpenVPN's response, up to the trailing `END`.
+        pub fn status(&mut self) -> io::Result<String> {
+            self.send_command("status")?;
+            self.read_reply()
+        }
+
+        /// Releases a hold previously requested with `--management-hold`.
+        pub fn release_hold(&mut self) -> io::Result<()> {
+            self.send_command("hold release")?;
+            self.read_reply().map(|_| ())
+        }
+
+        /// Answers a `--management-query-passwords` request with the given username and
+        /// password, so they never need to touch disk or the process argv.
+        pub fn send_credentials(&mut self, username: &str, password: &str) -> io::Result<()> {
+            self.send_command(&format!("username \"Auth\" \"{}\"", escape(username)))?;
+            self.read_reply()?;
+            self.send_command(&format!("password \"Auth\" \"{}\"", escape(password)))?;
+            self.read_reply().map(|_| ())
+        }
+
+        fn send_command(&mut self, command: &str) -> io::Result<()> {
+            self.connection.get_mut().write_all(command.as_bytes())?;
+            self.connection.get_mut().write_all(b"\n")
+        }
+
+        fn read_reply(&mut self) -> io::Result<String> {
+            let mut reply = String::new();
+            loop {
+                let mut line = String::new();
+                if self.connection.read_line(&mut line)? == 0 {
+                    return Ok(reply);
+                }
+                let trimmed = line.trim_end_matches(['\r', '\n']);
+                if trimmed == "END" {
+                    return Ok(reply);
+                }
+                // Single-line commands (`hold release`, `username`, `password`) get one
+                // `SUCCESS:`/`ERROR:` line back, with no trailing `END`.
+                let single_line = trimmed.starts_with("SUCCESS:") || trimmed.starts_with("ERROR:");
+                reply.push_str(&line);
+                if single_line && reply.len() == line.len() {
+                    return Ok(reply);
+                }
+            }
+        }
+    }
+
+    /// Escapes `\` and `"` so `value` can't break out of the quotes it's placed in when building
+    /// a management-interface command line, and strips `\r`/`\n` so it can't terminate that line
+    /// early and inject an arbitrary follow-up command.
+    fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"").replace(['\r', '\n'], "")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::ManagementEvent;
+
+        #[test]
+        fn parses_state_event() {
+            assert_eq!(ManagementEvent::parse(">STATE:1234,CONNECTED,SUCCESS"),
+                       ManagementEvent::State("1234,CONNECTED,SUCCESS".to_owned()));
+        }
+
+        #[test]
+        fn parses_hold_event() {
+            assert_eq!(ManagementEvent::parse(">HOLD:Waiting for hold release"),
+                       ManagementEvent::Hold("Waiting for hold release".to_owned()));
+        }
+
+        #[test]
+        fn parses_password_request_event() {
+            assert_eq!(ManagementEvent::parse(">PASSWORD:Need 'Auth' username/password"),
+                       ManagementEvent::PasswordRequest("Need 'Auth' username/password"
+                           .to_owned()));
+        }
+
+        #[test]
+        fn falls_back_to_other_for_unrecognized_lines() {
+            assert_eq!(ManagementEvent::parse("SUCCESS: hold released"),
+                       ManagementEvent::Other("SUCCESS: hold released".to_owned()));
+        }
+
+        #[test]
+        fn escape_quotes_and_backslashes() {
+            assert_eq!(super::escape("back\\slash and \"quote\""),
+                       "back\\\\slash and \\\"quote\\\"");
+        }
+
+        #[test]
+        fn escape_strips_newlines_to_prevent_command_injection() {
+            assert_eq!(super::escape("hunter2\r\nsignal SIGTERM"), "hunter2signal SIGTERM");
+        }
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::OpenVpnCommand;
     use net::RemoteAddr;
     use std::ffi::OsString;
+    use std::fs;
 
     #[test]
     fn no_arguments() {
@@ -159,8 +548,11 @@ mod tests {
 
         let testee_args = OpenVpnCommand::new("").remotes(remote).unwrap().get_arguments();
 
-        assert!(testee_args.contains(&OsString::from("example.com")));
-        assert!(testee_args.contains(&OsString::from("3333")));
+        assert_eq!(testee_args, vec![
+            OsString::from("--remote"),
+            OsString::from("example.com"),
+            OsString::from("3333"),
+        ]);
     }
 
     #[test]
@@ -169,10 +561,29 @@ mod tests {
 
         let testee_args = OpenVpnCommand::new("").remotes(&remotes[..]).unwrap().get_arguments();
 
-        assert!(testee_args.contains(&OsString::from("127.0.0.1")));
-        assert!(testee_args.contains(&OsString::from("998")));
-        assert!(testee_args.contains(&OsString::from("fe80::1")));
-        assert!(testee_args.contains(&OsString::from("1337")));
+        assert_eq!(testee_args, vec![
+            OsString::from("--remote"),
+            OsString::from("127.0.0.1"),
+            OsString::from("998"),
+            OsString::from("--remote"),
+            OsString::from("fe80::1"),
+            OsString::from("1337"),
+        ]);
+    }
+
+    #[test]
+    fn replacing_remotes_drops_the_old_ones() {
+        let mut command = OpenVpnCommand::new("");
+        command.remotes(RemoteAddr::new("127.0.0.1", 998)).unwrap();
+        command.remotes(RemoteAddr::new("fe80::1", 1337)).unwrap();
+
+        let testee_args = command.get_arguments();
+
+        assert_eq!(testee_args, vec![
+            OsString::from("--remote"),
+            OsString::from("fe80::1"),
+            OsString::from("1337"),
+        ]);
     }
 
     #[test]
@@ -186,9 +597,121 @@ mod tests {
 
         let testee_args = OpenVpnCommand::new("").remotes(&remotes[..]).unwrap().get_arguments();
 
-        assert!(testee_args.contains(&OsString::from("10.0.0.1")));
-        assert!(testee_args.contains(&OsString::from("1337")));
-        assert!(testee_args.contains(&OsString::from("127.0.0.1")));
-        assert!(testee_args.contains(&OsString::from("99")));
+        assert_eq!(testee_args, vec![
+            OsString::from("--remote"),
+            OsString::from("10.0.0.1"),
+            OsString::from("1337"),
+            OsString::from("--remote"),
+            OsString::from("127.0.0.1"),
+            OsString::from("99"),
+        ]);
+    }
+
+    #[test]
+    fn renders_config_file() {
+        let mut command = OpenVpnCommand::new("");
+        command.config("/etc/openvpn/test.conf");
+        command.remotes(RemoteAddr::new("example.com", 1194)).unwrap();
+        command.plugin("/usr/lib/openvpn/plugin.so", vec!["arg1".to_owned()]);
+
+        assert_eq!(command.to_config_file(), "config /etc/openvpn/test.conf\n\
+                                                remote example.com 1194\n\
+                                                plugin /usr/lib/openvpn/plugin.so arg1\n");
+    }
+
+    #[test]
+    fn sets_user_pass() {
+        let mut command = OpenVpnCommand::new("");
+        command.user_pass("my_user", "my_pass").unwrap();
+
+        let args = command.get_arguments();
+        assert_eq!(args[0], OsString::from("--auth-user-pass"));
+        let contents = fs::read_to_string(&args[1]).unwrap();
+        assert_eq!(contents, "my_user\nmy_pass\n");
+
+        assert!(command.to_config_file().starts_with("auth-user-pass "));
+    }
+
+    #[test]
+    fn sets_management_interface() {
+        let mut command = OpenVpnCommand::new("");
+        command.management_interface("127.0.0.1", super::ManagementSocket::Port(7505), true, true,
+                                      false);
+
+        assert_eq!(command.get_arguments(), vec![
+            OsString::from("--management"),
+            OsString::from("127.0.0.1"),
+            OsString::from("7505"),
+            OsString::from("--management-client"),
+            OsString::from("--management-hold"),
+        ]);
+    }
+
+    #[test]
+    fn sets_management_query_passwords_only_when_requested() {
+        let mut command = OpenVpnCommand::new("");
+        command.management_interface("/tmp/openvpn.sock", super::ManagementSocket::Unix, false,
+                                      false, true);
+
+        assert_eq!(command.get_arguments(), vec![
+            OsString::from("--management"),
+            OsString::from("/tmp/openvpn.sock"),
+            OsString::from("unix"),
+            OsString::from("--management-query-passwords"),
+        ]);
+    }
+
+    #[test]
+    fn quotes_config_tokens_containing_whitespace() {
+        let mut command = OpenVpnCommand::new("");
+        command.plugin("/usr/lib/openvpn/plugin.so", vec!["arg with space".to_owned()]);
+        command.up("C:\\Program Files\\OpenVPN\\up.bat");
+
+        assert_eq!(command.to_config_file(),
+                   "plugin /usr/lib/openvpn/plugin.so \"arg with space\"\n\
+                    up \"C:\\Program Files\\OpenVPN\\up.bat\"\n");
+    }
+
+    #[test]
+    fn sets_protocol() {
+        let testee_args = OpenVpnCommand::new("").protocol(super::Protocol::Udp).get_arguments();
+
+        assert_eq!(testee_args, vec![OsString::from("--proto"), OsString::from("udp")]);
+    }
+
+    #[test]
+    fn generic_option_keeps_insertion_order_relative_to_typed_options() {
+        let mut command = OpenVpnCommand::new("");
+        command.option("--cipher", vec![OsString::from("AES-256-GCM")]);
+        command.config("/etc/openvpn/test.conf");
+        command.option("--redirect-gateway", vec![]);
+
+        assert_eq!(command.get_arguments(), vec![
+            OsString::from("--cipher"),
+            OsString::from("AES-256-GCM"),
+            OsString::from("--config"),
+            OsString::from("/etc/openvpn/test.conf"),
+            OsString::from("--redirect-gateway"),
+        ]);
+    }
+
+    #[test]
+    fn sets_lifecycle_hook_scripts() {
+        let mut command = OpenVpnCommand::new("");
+        command.up("/etc/openvpn/up.sh");
+        command.down("/etc/openvpn/down.sh");
+        command.route_up("/etc/openvpn/route-up.sh");
+        command.script_security(2);
+
+        assert_eq!(command.get_arguments(), vec![
+            OsString::from("--up"),
+            OsString::from("/etc/openvpn/up.sh"),
+            OsString::from("--down"),
+            OsString::from("/etc/openvpn/down.sh"),
+            OsString::from("--route-up"),
+            OsString::from("/etc/openvpn/route-up.sh"),
+            OsString::from("--script-security"),
+            OsString::from("2"),
+        ]);
     }
 }
\ No newline at end of file